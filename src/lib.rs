@@ -1,54 +1,392 @@
 use fuzzy_matcher::FuzzyMatcher;
 use fuzzy_matcher::skim::SkimMatcherV2;
 use thiserror::Error;
+use unicode_normalization::UnicodeNormalization;
 
 #[derive(Error, Debug, PartialEq)]
 pub enum SkimpleError {
     #[error("Unable to find needle in haystack")]
     NeedleNotFoundError,
+}
+
+/// Which matching algorithm [`SkimpleMatcher`] should use when scoring a haystack item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    /// Score with the underlying fuzzy scorer (the default, matches `fuzzy` and friends).
+    Fuzzy,
+    /// Match if the needle appears anywhere in the item.
+    Substring,
+    /// Match if the item starts with the needle.
+    Prefix,
+    /// Match if the item equals the needle exactly.
+    Exact,
+}
 
-    // This *won't* happen, but I'd rather not panic
-    #[error("Needle disappeared from haystack")]
-    NeedleDisappearedError,
+/// How [`SkimpleMatcher`] should handle case when comparing under [`MatchKind::Substring`],
+/// [`MatchKind::Prefix`], or [`MatchKind::Exact`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseMatching {
+    /// Case must match exactly.
+    Respect,
+    /// Case is folded away before comparing.
+    Ignore,
+    /// Case-insensitive unless the needle contains an uppercase character.
+    Smart,
+}
+
+/// Configuration for a [`SkimpleMatcher`], controlling which algorithm it uses and how it
+/// treats case and Unicode normalization.
+#[derive(Debug, Clone, Copy)]
+pub struct SkimpleConfig {
+    pub match_kind: MatchKind,
+    pub case_matching: CaseMatching,
+    /// When `true`, haystack and needle are decomposed to NFD and stripped of combining marks
+    /// before comparing, so e.g. `"cafe"` matches `"café"`. Applies only to
+    /// [`MatchKind::Substring`], [`MatchKind::Prefix`], and [`MatchKind::Exact`];
+    /// [`MatchKind::Fuzzy`] scores the strings as given.
+    pub normalize: bool,
+}
+
+impl Default for SkimpleConfig {
+    fn default() -> Self {
+        SkimpleConfig {
+            match_kind: MatchKind::Fuzzy,
+            case_matching: CaseMatching::Smart,
+            normalize: false,
+        }
+    }
 }
 
 pub struct SkimpleMatcher {
     matcher: SkimMatcherV2,
+    config: SkimpleConfig,
 }
 
 impl SkimpleMatcher {
     pub fn default() -> Self {
         SkimpleMatcher {
             matcher: SkimMatcherV2::default(),
+            config: SkimpleConfig::default(),
         }
     }
 
     pub fn new(matcher: SkimMatcherV2) -> Self {
-        SkimpleMatcher { matcher }
+        SkimpleMatcher {
+            matcher,
+            config: SkimpleConfig::default(),
+        }
     }
 
-    pub fn fuzzy<'a, 'b>(
+    /// Builds a matcher that scores with `match_kind`/`case_matching`/`normalize` from `config`
+    /// instead of always delegating to the fuzzy scorer.
+    pub fn with_config(matcher: SkimMatcherV2, config: SkimpleConfig) -> Self {
+        SkimpleMatcher { matcher, config }
+    }
+
+    fn ignore_case(&self, needle: &str) -> bool {
+        match self.config.case_matching {
+            CaseMatching::Respect => false,
+            CaseMatching::Ignore => true,
+            CaseMatching::Smart => !needle.chars().any(|c| c.is_uppercase()),
+        }
+    }
+
+    fn prepare(&self, s: &str, ignore_case: bool) -> String {
+        self.prepare_with_map(s, ignore_case).0
+    }
+
+    /// Like [`Self::prepare`], but also returns, for each char of the prepared string, the char
+    /// index in `s` it was produced from. Used to translate a match position found in the
+    /// prepared string back to a position in the original, unprepared one.
+    fn prepare_with_map(&self, s: &str, ignore_case: bool) -> (String, Vec<usize>) {
+        let mut prepared = String::new();
+        let mut map = Vec::new();
+
+        for (index, c) in s.chars().enumerate() {
+            let decomposed: Vec<char> = if self.config.normalize {
+                c.nfd()
+                    .filter(|nc| unicode_normalization::char::canonical_combining_class(*nc) == 0)
+                    .collect()
+            } else {
+                vec![c]
+            };
+
+            for dc in decomposed {
+                let folded: Vec<char> = if ignore_case {
+                    dc.to_lowercase().collect()
+                } else {
+                    vec![dc]
+                };
+
+                for fc in folded {
+                    prepared.push(fc);
+                    map.push(index);
+                }
+            }
+        }
+
+        (prepared, map)
+    }
+
+    /// Finds where `needle` matches `item` under [`MatchKind::Substring`], [`MatchKind::Prefix`],
+    /// or [`MatchKind::Exact`], and returns the char indices in `item` the match covers.
+    fn direct_match_indices(&self, item: &str, needle: &str) -> Option<Vec<usize>> {
+        let ignore_case = self.ignore_case(needle);
+        let (haystack, map) = self.prepare_with_map(item, ignore_case);
+        let needle = self.prepare(needle, ignore_case);
+
+        let start_byte = match self.config.match_kind {
+            MatchKind::Substring => haystack.find(&needle)?,
+            MatchKind::Prefix => haystack.starts_with(&needle).then_some(0)?,
+            MatchKind::Exact => (haystack == needle).then_some(0)?,
+            MatchKind::Fuzzy => unreachable!(),
+        };
+
+        let start_char = haystack[..start_byte].chars().count();
+        let match_char_len = needle.chars().count();
+
+        let mut indices: Vec<usize> = map[start_char..start_char + match_char_len].to_vec();
+        indices.dedup();
+
+        Some(indices)
+    }
+
+    /// Scores `item` against `needle` according to `self.config`, delegating to the fuzzy
+    /// scorer for [`MatchKind::Fuzzy`] and matching directly otherwise. Direct matching always
+    /// goes through `&str` methods (`contains`/`starts_with`/`==`), so multi-byte needles and
+    /// haystacks are compared on char boundaries and can never be truncated mid-codepoint.
+    fn score(&self, item: &str, needle: &str) -> Option<i64> {
+        match self.config.match_kind {
+            MatchKind::Fuzzy => self.matcher.fuzzy_match(item, needle),
+            MatchKind::Substring | MatchKind::Prefix | MatchKind::Exact => {
+                let ignore_case = self.ignore_case(needle);
+                let haystack = self.prepare(item, ignore_case);
+                let needle = self.prepare(needle, ignore_case);
+
+                let matched = match self.config.match_kind {
+                    MatchKind::Substring => haystack.contains(&needle),
+                    MatchKind::Prefix => haystack.starts_with(&needle),
+                    MatchKind::Exact => haystack == needle,
+                    MatchKind::Fuzzy => unreachable!(),
+                };
+
+                matched.then(|| needle.chars().count() as i64)
+            }
+        }
+    }
+
+    pub fn fuzzy<'a>(
         &self,
-        haystack: &'a [&'a str],
-        needle: &'b str,
+        haystack: &[&'a str],
+        needle: &str,
     ) -> Result<&'a str, SkimpleError> {
-        let results: Vec<i64> = haystack
+        self.fuzzy_filter(haystack, needle, i64::MIN)
+            .first()
+            .map(|(item, _)| *item)
+            .ok_or(SkimpleError::NeedleNotFoundError)
+    }
+
+    /// Scores every item in `haystack` against `needle` (per `self.config`), keeps only the
+    /// items whose score is `Some(score)` with `score >= min_score`, and returns them as
+    /// `(item, score)` pairs sorted by descending score (ties broken by original index), so
+    /// callers can set a tunable relevance cutoff.
+    pub fn fuzzy_filter<'a>(
+        &self,
+        haystack: &[&'a str],
+        needle: &str,
+        min_score: i64,
+    ) -> Vec<(&'a str, i64)> {
+        let mut results: Vec<(usize, &'a str, i64)> = haystack
             .iter()
-            .map(|item| self.matcher.fuzzy_match(item, needle).unwrap_or(0))
+            .enumerate()
+            .filter_map(|(index, item)| {
+                self.score(item, needle)
+                    .filter(|score| *score >= min_score)
+                    .map(|score| (index, *item, score))
+            })
             .collect();
 
-        if results.iter().sum::<i64>() == 0 {
-            return Err(SkimpleError::NeedleNotFoundError);
-        }
+        results.sort_by(|(index_a, _, score_a), (index_b, _, score_b)| {
+            score_b.cmp(score_a).then(index_a.cmp(index_b))
+        });
+
+        results
+            .into_iter()
+            .map(|(_, item, score)| (item, score))
+            .collect()
+    }
 
-        let result: &str = haystack[results
+    /// Finds the best-matching string in `haystack`, like [`Self::fuzzy`], but also returns the
+    /// indices within that string that `needle` matched, so callers can highlight them.
+    pub fn fuzzy_with_indices<'a>(
+        &self,
+        haystack: &[&'a str],
+        needle: &str,
+    ) -> Result<(&'a str, Vec<usize>), SkimpleError> {
+        let mut results: Vec<(usize, i64, Vec<usize>)> = haystack
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| match self.config.match_kind {
+                MatchKind::Fuzzy => self
+                    .matcher
+                    .fuzzy_indices(item, needle)
+                    .map(|(score, indices)| (index, score, indices)),
+                MatchKind::Substring | MatchKind::Prefix | MatchKind::Exact => self
+                    .score(item, needle)
+                    .zip(self.direct_match_indices(item, needle))
+                    .map(|(score, indices)| (index, score, indices)),
+            })
+            .collect();
+
+        results.sort_by(|(index_a, score_a, _), (index_b, score_b, _)| {
+            score_b.cmp(score_a).then(index_a.cmp(index_b))
+        });
+
+        let (index, _, indices) = results
+            .into_iter()
+            .next()
+            .ok_or(SkimpleError::NeedleNotFoundError)?;
+
+        Ok((haystack[index], indices))
+    }
+
+    /// Scores every item in `haystack` against `needle`, drops the items that don't match at
+    /// all, and returns the top `n` `(item, score)` pairs sorted by descending score. Ties are
+    /// broken by original index, so the result order is stable.
+    pub fn fuzzy_top_n<'a>(
+        &self,
+        haystack: &[&'a str],
+        needle: &str,
+        n: usize,
+    ) -> Vec<(&'a str, i64)> {
+        let mut results = self.fuzzy_filter(haystack, needle, i64::MIN);
+        results.truncate(n);
+        results
+    }
+
+    /// Like [`Self::fuzzy`], but scores `key(item)` for each item instead of requiring callers
+    /// to pre-build a parallel `Vec<&str>` and re-locate the winner by string equality.
+    pub fn fuzzy_by<'a, T>(
+        &self,
+        items: &'a [T],
+        needle: &str,
+        key: impl Fn(&T) -> &str,
+    ) -> Result<&'a T, SkimpleError> {
+        self.fuzzy_filter_by(items, needle, i64::MIN, key)
+            .first()
+            .map(|(item, _)| *item)
+            .ok_or(SkimpleError::NeedleNotFoundError)
+    }
+
+    /// Like [`Self::fuzzy_filter`], but scores `key(item)` for each item and returns the
+    /// original items rather than the derived strings.
+    pub fn fuzzy_filter_by<'a, T>(
+        &self,
+        items: &'a [T],
+        needle: &str,
+        min_score: i64,
+        key: impl Fn(&T) -> &str,
+    ) -> Vec<(&'a T, i64)> {
+        let mut results: Vec<(usize, &'a T, i64)> = items
             .iter()
             .enumerate()
-            .max_by(|(_, a), (_, b)| a.cmp(b))
-            .map(|(index, _)| index)
-            .ok_or(SkimpleError::NeedleDisappearedError)?];
+            .filter_map(|(index, item)| {
+                self.score(key(item), needle)
+                    .filter(|score| *score >= min_score)
+                    .map(|score| (index, item, score))
+            })
+            .collect();
 
-        Ok(result)
+        results.sort_by(|(index_a, _, score_a), (index_b, _, score_b)| {
+            score_b.cmp(score_a).then(index_a.cmp(index_b))
+        });
+
+        results
+            .into_iter()
+            .map(|(_, item, score)| (item, score))
+            .collect()
+    }
+
+    /// Like [`Self::fuzzy_top_n`], but scores `key(item)` for each item and returns the original
+    /// items rather than the derived strings.
+    pub fn fuzzy_by_top_n<'a, T>(
+        &self,
+        items: &'a [T],
+        needle: &str,
+        n: usize,
+        key: impl Fn(&T) -> &str,
+    ) -> Vec<(&'a T, i64)> {
+        let mut results = self.fuzzy_filter_by(items, needle, i64::MIN, key);
+        results.truncate(n);
+        results
+    }
+}
+
+/// A matcher whose haystack is built up over time instead of being known up front, for pickers
+/// that stream items in from e.g. a directory walk or subprocess. Items are retained internally
+/// so repeated queries as the user types don't reallocate the corpus.
+pub struct SkimpleStream {
+    matcher: SkimpleMatcher,
+    items: Vec<String>,
+    // Borrows of the `String`s in `items`, cached so query methods don't reallocate a `Vec<&str>`
+    // view of the corpus on every keystroke.
+    //
+    // SAFETY: `items` only ever grows (never truncated, removed from, or mutated in place), and
+    // a `String`'s heap buffer doesn't move when the `Vec<String>` that owns it reallocates, so a
+    // pointer into one of its bytes stays valid for the lifetime of `self` even as `items` grows.
+    // `views` never outlives `self` (it's dropped alongside `items`) and is only ever handed out
+    // reborrowed to a shorter, `&self`-scoped lifetime, so the `'static` here is not a real claim
+    // about how long the data lives, just a way to store a self-referential borrow.
+    views: Vec<&'static str>,
+}
+
+impl SkimpleStream {
+    pub fn new() -> Self {
+        SkimpleStream {
+            matcher: SkimpleMatcher::default(),
+            items: Vec::new(),
+            views: Vec::new(),
+        }
+    }
+
+    /// Like [`Self::new`], but scores with `matcher` instead of the default fuzzy matcher.
+    pub fn with_matcher(matcher: SkimpleMatcher) -> Self {
+        SkimpleStream {
+            matcher,
+            items: Vec::new(),
+            views: Vec::new(),
+        }
+    }
+
+    /// Adds `item` to the retained corpus.
+    pub fn inject(&mut self, item: String) {
+        self.items.push(item);
+
+        let view = self.items.last().expect("just pushed").as_str();
+        // SAFETY: see the `views` field comment.
+        self.views.push(unsafe { &*(view as *const str) });
+    }
+
+    /// The number of items injected so far, so a caller can show progress while injection is
+    /// ongoing.
+    pub fn active_items(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Finds the best match for `needle` among the items injected so far.
+    pub fn best(&self, needle: &str) -> Result<&str, SkimpleError> {
+        self.matcher.fuzzy(&self.views, needle)
+    }
+
+    /// Finds the top `n` matches for `needle` among the items injected so far.
+    pub fn top_n(&self, needle: &str, n: usize) -> Vec<(&str, i64)> {
+        self.matcher.fuzzy_top_n(&self.views, needle, n)
+    }
+}
+
+impl Default for SkimpleStream {
+    fn default() -> Self {
+        SkimpleStream::new()
     }
 }
 
@@ -75,4 +413,214 @@ mod tests {
         let result = matcher.fuzzy(&haystack, &needle);
         assert_eq!(result, Err(SkimpleError::NeedleNotFoundError));
     }
+
+    #[test]
+    fn top_n() {
+        let matcher = SkimpleMatcher::default();
+        let haystack = ["Mort", "Sourcery", "Wyrd Sisters", "Pyramids", "Guards! Guards!"];
+        let needle = "or";
+
+        let result = matcher.fuzzy_top_n(&haystack, &needle, 2);
+        assert_eq!(result[0].0, "Mort");
+        assert_eq!(result.len(), 2);
+    }
+
+    #[test]
+    fn with_indices() {
+        let matcher = SkimpleMatcher::default();
+        let haystack = ["Mort", "Sourcery", "Wyrd Sisters", "Pyramids", "Guards! Guards!"];
+        let needle = "gards";
+
+        let (result, indices) = matcher.fuzzy_with_indices(&haystack, &needle).unwrap();
+        assert_eq!(result, "Guards! Guards!");
+        assert!(!indices.is_empty());
+    }
+
+    #[test]
+    fn with_indices_breaks_ties_like_fuzzy() {
+        let matcher = SkimpleMatcher::default();
+        let haystack = ["aXb", "aYb"];
+        let needle = "ab";
+
+        let fuzzy_result = matcher.fuzzy(&haystack, &needle);
+        let (with_indices_result, _) = matcher.fuzzy_with_indices(&haystack, &needle).unwrap();
+
+        assert_eq!(fuzzy_result, Ok(with_indices_result));
+    }
+
+    #[test]
+    fn with_indices_respects_config() {
+        let matcher = SkimpleMatcher::with_config(
+            SkimMatcherV2::default(),
+            SkimpleConfig {
+                match_kind: MatchKind::Exact,
+                ..SkimpleConfig::default()
+            },
+        );
+        let haystack = ["Mort", "Sourcery", "Wyrd Sisters", "Pyramids", "Guards! Guards!"];
+
+        assert_eq!(
+            matcher.fuzzy(&haystack, "gards"),
+            Err(SkimpleError::NeedleNotFoundError)
+        );
+        assert_eq!(
+            matcher.fuzzy_with_indices(&haystack, "gards"),
+            Err(SkimpleError::NeedleNotFoundError)
+        );
+
+        let (result, indices) = matcher.fuzzy_with_indices(&haystack, "mort").unwrap();
+        assert_eq!(result, "Mort");
+        assert_eq!(indices, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn by_key() {
+        struct Book {
+            title: &'static str,
+        }
+
+        let matcher = SkimpleMatcher::default();
+        let books = [
+            Book { title: "Mort" },
+            Book { title: "Sourcery" },
+            Book { title: "Guards! Guards!" },
+        ];
+        let needle = "gards";
+
+        let result = matcher.fuzzy_by(&books, &needle, |book| book.title);
+        assert_eq!(result.unwrap().title, "Guards! Guards!");
+
+        let top = matcher.fuzzy_by_top_n(&books, &needle, 1, |book| book.title);
+        assert_eq!(top[0].0.title, "Guards! Guards!");
+    }
+
+    #[test]
+    fn by_key_empty_needle_matches_zero_score() {
+        struct Book {
+            title: &'static str,
+        }
+
+        let matcher = SkimpleMatcher::default();
+        let books = [Book { title: "Mort" }, Book { title: "Sourcery" }];
+
+        let result = matcher.fuzzy_by(&books, "", |book| book.title);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn substring_match_kind() {
+        let matcher = SkimpleMatcher::with_config(
+            SkimMatcherV2::default(),
+            SkimpleConfig {
+                match_kind: MatchKind::Substring,
+                ..SkimpleConfig::default()
+            },
+        );
+        let haystack = ["Mort", "Sourcery", "Wyrd Sisters", "Pyramids", "Guards! Guards!"];
+
+        let result = matcher.fuzzy(&haystack, "guards!");
+        assert_eq!(result, Ok("Guards! Guards!"));
+
+        let result = matcher.fuzzy(&haystack, "nope");
+        assert_eq!(result, Err(SkimpleError::NeedleNotFoundError));
+    }
+
+    #[test]
+    fn prefix_match_kind() {
+        let matcher = SkimpleMatcher::with_config(
+            SkimMatcherV2::default(),
+            SkimpleConfig {
+                match_kind: MatchKind::Prefix,
+                ..SkimpleConfig::default()
+            },
+        );
+        let haystack = ["Mort", "Sourcery", "Wyrd Sisters", "Pyramids", "Guards! Guards!"];
+
+        let result = matcher.fuzzy(&haystack, "guards");
+        assert_eq!(result, Ok("Guards! Guards!"));
+
+        let result = matcher.fuzzy(&haystack, "ards");
+        assert_eq!(result, Err(SkimpleError::NeedleNotFoundError));
+    }
+
+    #[test]
+    fn exact_match_kind_respects_case() {
+        let matcher = SkimpleMatcher::with_config(
+            SkimMatcherV2::default(),
+            SkimpleConfig {
+                match_kind: MatchKind::Exact,
+                case_matching: CaseMatching::Respect,
+                ..SkimpleConfig::default()
+            },
+        );
+        let haystack = ["Mort", "mort"];
+
+        let result = matcher.fuzzy(&haystack, "mort");
+        assert_eq!(result, Ok("mort"));
+    }
+
+    #[test]
+    fn normalized_substring_match() {
+        let matcher = SkimpleMatcher::with_config(
+            SkimMatcherV2::default(),
+            SkimpleConfig {
+                match_kind: MatchKind::Substring,
+                normalize: true,
+                ..SkimpleConfig::default()
+            },
+        );
+        let haystack = ["café", "Pyramids"];
+
+        let result = matcher.fuzzy(&haystack, "cafe");
+        assert_eq!(result, Ok("café"));
+    }
+
+    #[test]
+    fn stream_tracks_injected_items() {
+        let mut stream = SkimpleStream::new();
+        assert_eq!(stream.active_items(), 0);
+
+        for title in ["Mort", "Sourcery", "Wyrd Sisters", "Pyramids", "Guards! Guards!"] {
+            stream.inject(title.to_string());
+        }
+        assert_eq!(stream.active_items(), 5);
+
+        let result = stream.best("gards");
+        assert_eq!(result, Ok("Guards! Guards!"));
+
+        let top = stream.top_n("gards", 1);
+        assert_eq!(top[0].0, "Guards! Guards!");
+    }
+
+    #[test]
+    fn stream_views_stay_valid_across_reallocation() {
+        let mut stream = SkimpleStream::new();
+
+        // Zero-padded so every entry has the same length and none is a fuzzy (subsequence) match
+        // for another, so each lookup of an entry's full text matches only that entry.
+        for i in 0..1000 {
+            stream.inject(format!("item-{i:04}"));
+        }
+        assert_eq!(stream.active_items(), 1000);
+
+        // Forces the backing `Vec<String>` through several reallocations; if a view had been
+        // invalidated by one, these lookups would read garbage or panic instead of matching.
+        assert_eq!(stream.best("item-0000"), Ok("item-0000"));
+        assert_eq!(stream.best("item-0500"), Ok("item-0500"));
+        assert_eq!(stream.best("item-0999"), Ok("item-0999"));
+    }
+
+    #[test]
+    fn filter_by_min_score() {
+        let matcher = SkimpleMatcher::default();
+        let haystack = ["Mort", "Sourcery", "Wyrd Sisters", "Pyramids", "Guards! Guards!"];
+        let needle = "gards";
+
+        let all = matcher.fuzzy_filter(&haystack, &needle, i64::MIN);
+        let best_score = all[0].1;
+
+        let filtered = matcher.fuzzy_filter(&haystack, &needle, best_score);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].0, "Guards! Guards!");
+    }
 }